@@ -17,6 +17,7 @@ use crate::tools::registry::ToolKind;
 pub struct ListDirHandler;
 
 const MAX_ENTRY_LENGTH: usize = 500;
+const GITIGNORE_FILE_NAME: &str = ".gitignore";
 
 fn default_offset() -> usize {
     1
@@ -39,6 +40,164 @@ struct ListDirArgs {
     limit: usize,
     #[serde(default = "default_depth")]
     depth: usize,
+    /// Glob patterns (matched against the entry's path relative to `dir_path`,
+    /// `**` spans directory boundaries) whose matches are dropped from the
+    /// listing. A matching directory is pruned from traversal entirely.
+    #[serde(default)]
+    exclude_globs: Vec<String>,
+    /// Glob patterns that a file must match to be kept in the listing.
+    /// Directories are always kept (when not excluded) so the tree structure
+    /// leading to a match stays visible.
+    #[serde(default)]
+    include_globs: Vec<String>,
+    /// When true, apply standard `.gitignore` semantics while descending:
+    /// rules accumulate from parent directories, the last matching pattern
+    /// wins, and a leading `!` negates. Ignored directories are pruned.
+    #[serde(default)]
+    respect_gitignore: bool,
+    /// When true, descend into symlinks that resolve to a directory instead
+    /// of leaving them as an opaque `[symlink]` leaf. Guarded against cycles.
+    #[serde(default)]
+    follow_symlinks: bool,
+    /// Extra columns to render after the kind label, e.g. `size`, `mtime`,
+    /// `mode`. Unknown names are ignored.
+    #[serde(default)]
+    fields: Vec<String>,
+    /// Key to sort entries by before paging. Defaults to `name`.
+    #[serde(default = "default_sort_by")]
+    sort_by: SortBy,
+    /// When true, reverse the sort order.
+    #[serde(default)]
+    reverse: bool,
+    /// Skip entries shallower than this depth (1 = direct children of
+    /// `dir_path`), so callers can list only the deep entries without the
+    /// shallow directory noise.
+    #[serde(default)]
+    min_depth: usize,
+    /// Selects between a normal listing and a content-addressed Merkle
+    /// digest of the whole tree. `depth` is ignored in digest mode, since a
+    /// partial walk would produce a wrong hash.
+    #[serde(default = "default_mode")]
+    mode: ListDirMode,
+    /// In digest mode, also return the full relative-path -> digest map
+    /// alongside the root digest, so callers can diff two trees by
+    /// comparing only the subtrees whose digests differ.
+    #[serde(default)]
+    include_digest_map: bool,
+}
+
+#[derive(Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+enum ListDirMode {
+    List,
+    Digest,
+}
+
+fn default_mode() -> ListDirMode {
+    ListDirMode::List
+}
+
+#[derive(Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+enum SortBy {
+    #[default]
+    Name,
+    Size,
+    Mtime,
+}
+
+fn default_sort_by() -> SortBy {
+    SortBy::Name
+}
+
+/// Filters applied while walking the tree, threaded through [`collect_entries`]
+/// so new filtering knobs don't keep growing that function's parameter list.
+#[derive(Default)]
+struct CollectOptions {
+    exclude_globs: Vec<String>,
+    include_globs: Vec<String>,
+    respect_gitignore: bool,
+    follow_symlinks: bool,
+    fields: Vec<String>,
+    sort_by: SortBy,
+    reverse: bool,
+    min_depth: usize,
+}
+
+/// Metadata collected for an entry when it's needed to render a requested
+/// field or to sort by something other than name.
+#[derive(Clone, Default)]
+struct EntryMetadata {
+    size: u64,
+    mtime_secs: i64,
+    mode: Option<u32>,
+}
+
+async fn entry_metadata(path: &Path) -> Option<EntryMetadata> {
+    let metadata = fs::metadata(path).await.ok()?;
+    let mtime_secs = metadata
+        .modified()
+        .ok()
+        .and_then(|modified| modified.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|duration| duration.as_secs() as i64)
+        .unwrap_or(0);
+
+    #[cfg(unix)]
+    let mode = {
+        use std::os::unix::fs::PermissionsExt;
+        Some(metadata.permissions().mode() & 0o7777)
+    };
+    #[cfg(not(unix))]
+    let mode = None;
+
+    Some(EntryMetadata {
+        size: metadata.len(),
+        mtime_secs,
+        mode,
+    })
+}
+
+fn render_fields(fields: &[String], metadata: Option<&EntryMetadata>) -> Option<String> {
+    let metadata = metadata?;
+    let rendered: Vec<String> = fields
+        .iter()
+        .filter_map(|field| match field.as_str() {
+            "size" => Some(format!("size={}", metadata.size)),
+            "mtime" => Some(format!("mtime={}", metadata.mtime_secs)),
+            "mode" => metadata.mode.map(|mode| format!("mode={mode:o}")),
+            _ => None,
+        })
+        .collect();
+
+    if rendered.is_empty() {
+        None
+    } else {
+        Some(rendered.join(" "))
+    }
+}
+
+/// Identity of a directory used to detect symlink cycles: `(dev, ino)` on
+/// Unix, or a canonicalized path where that extension isn't available.
+#[cfg(unix)]
+type DirIdentity = (u64, u64);
+#[cfg(not(unix))]
+type DirIdentity = PathBuf;
+
+#[cfg(unix)]
+fn identity_from_metadata(metadata: &std::fs::Metadata) -> DirIdentity {
+    use std::os::unix::fs::MetadataExt;
+    (metadata.dev(), metadata.ino())
+}
+
+#[cfg(unix)]
+async fn dir_identity(path: &Path, metadata: &std::fs::Metadata) -> std::io::Result<DirIdentity> {
+    let _ = path;
+    Ok(identity_from_metadata(metadata))
+}
+
+#[cfg(not(unix))]
+async fn dir_identity(path: &Path, _metadata: &std::fs::Metadata) -> std::io::Result<DirIdentity> {
+    fs::canonicalize(path).await
 }
 
 #[async_trait]
@@ -70,8 +229,55 @@ impl ToolHandler for ListDirHandler {
             offset,
             limit,
             depth,
+            exclude_globs,
+            include_globs,
+            respect_gitignore,
+            follow_symlinks,
+            fields,
+            sort_by,
+            reverse,
+            min_depth,
+            mode,
+            include_digest_map,
         } = args;
 
+        let path = PathBuf::from(&dir_path);
+        if !path.is_absolute() {
+            return Err(FunctionCallError::RespondToModel(
+                "dir_path must be an absolute path".to_string(),
+            ));
+        }
+
+        let options = CollectOptions {
+            exclude_globs,
+            include_globs,
+            respect_gitignore,
+            follow_symlinks,
+            fields,
+            sort_by,
+            reverse,
+            min_depth,
+        };
+
+        if mode == ListDirMode::Digest {
+            let mut digest_map = Vec::new();
+            let root = compute_digest(&path, Path::new(""), Vec::new(), &options, include_digest_map, &mut digest_map)
+                .await?;
+
+            let mut content = format!("root: {}", root.to_hex());
+            if include_digest_map {
+                digest_map.sort_by(|a, b| a.0.cmp(&b.0));
+                for (relative_path, digest) in &digest_map {
+                    content.push_str(&format!("\n{relative_path}: {}", digest.to_hex()));
+                }
+            }
+
+            return Ok(ToolOutput::Function {
+                content,
+                success: Some(true),
+            });
+        }
+
         if offset == 0 {
             return Err(FunctionCallError::RespondToModel(
                 "offset must be a 1-indexed entry number".to_string(),
@@ -90,14 +296,7 @@ impl ToolHandler for ListDirHandler {
             ));
         }
 
-        let path = PathBuf::from(&dir_path);
-        if !path.is_absolute() {
-            return Err(FunctionCallError::RespondToModel(
-                "dir_path must be an absolute path".to_string(),
-            ));
-        }
-
-        let entries = list_dir_slice(&path, offset, limit, depth).await?;
+        let entries = list_dir_slice(&path, offset, limit, depth, &options).await?;
         Ok(ToolOutput::Function {
             content: entries.join("\n"),
             success: Some(true),
@@ -110,11 +309,23 @@ async fn list_dir_slice(
     offset: usize,
     limit: usize,
     depth: usize,
+    options: &CollectOptions,
 ) -> Result<Vec<String>, FunctionCallError> {
     let mut entries = Vec::new();
-    collect_entries(path, Path::new(""), depth, &mut entries).await?;
+    collect_entries(
+        path,
+        Path::new(""),
+        depth,
+        Vec::new(),
+        options,
+        &mut entries,
+    )
+    .await?;
 
-    entries.sort_unstable_by(|a, b| a.name.cmp(&b.name));
+    entries.sort_unstable_by(|a, b| compare_entries(a, b, options.sort_by));
+    if options.reverse {
+        entries.reverse();
+    }
 
     if entries.is_empty() {
         return Ok(Vec::new());
@@ -132,21 +343,77 @@ async fn list_dir_slice(
 
     for (position, entry) in entries[start_index..end_index].iter().enumerate() {
         let ordinal = start_index + position + 1;
-        formatted.push(format!("E{ordinal}: {} {}", entry.kind.label(), entry.name));
+        match render_fields(&options.fields, entry.metadata.as_ref()) {
+            Some(fields) => formatted.push(format!(
+                "E{ordinal}: {} {fields} {}",
+                entry.kind.label(),
+                entry.name
+            )),
+            None => formatted.push(format!("E{ordinal}: {} {}", entry.kind.label(), entry.name)),
+        }
     }
 
     Ok(formatted)
 }
 
+fn compare_entries(a: &DirEntry, b: &DirEntry, sort_by: SortBy) -> std::cmp::Ordering {
+    let key_ordering = match sort_by {
+        SortBy::Name => std::cmp::Ordering::Equal,
+        SortBy::Size => a
+            .metadata
+            .as_ref()
+            .map(|m| m.size)
+            .unwrap_or(0)
+            .cmp(&b.metadata.as_ref().map(|m| m.size).unwrap_or(0)),
+        SortBy::Mtime => a
+            .metadata
+            .as_ref()
+            .map(|m| m.mtime_secs)
+            .unwrap_or(0)
+            .cmp(&b.metadata.as_ref().map(|m| m.mtime_secs).unwrap_or(0)),
+    };
+    key_ordering.then_with(|| a.name.cmp(&b.name))
+}
+
 async fn collect_entries(
     dir_path: &Path,
     relative_prefix: &Path,
     depth: usize,
+    initial_rules: Vec<IgnoreRule>,
+    options: &CollectOptions,
     entries: &mut Vec<DirEntry>,
 ) -> Result<(), FunctionCallError> {
-    let mut stack = vec![(dir_path.to_path_buf(), relative_prefix.to_path_buf(), depth)];
+    let mut stack = vec![(
+        dir_path.to_path_buf(),
+        relative_prefix.to_path_buf(),
+        depth,
+        initial_rules,
+        Vec::<DirIdentity>::new(),
+    )];
+
+    while let Some((current_dir, prefix, remaining_depth, parent_rules, parent_ancestors)) =
+        stack.pop()
+    {
+        let rules = if options.respect_gitignore {
+            let mut rules = parent_rules;
+            rules.extend(load_gitignore_rules(&current_dir, &prefix).await);
+            rules
+        } else {
+            parent_rules
+        };
+
+        let ancestors = if options.follow_symlinks {
+            let mut ancestors = parent_ancestors;
+            if let Ok(metadata) = fs::metadata(&current_dir).await {
+                if let Ok(identity) = dir_identity(&current_dir, &metadata).await {
+                    ancestors.push(identity);
+                }
+            }
+            ancestors
+        } else {
+            parent_ancestors
+        };
 
-    while let Some((current_dir, prefix, remaining_depth)) = stack.pop() {
         let mut read_dir = fs::read_dir(&current_dir).await.map_err(|err| {
             FunctionCallError::RespondToModel(format!("failed to read directory: {err}"))
         })?;
@@ -165,15 +432,77 @@ async fn collect_entries(
                 prefix.join(&file_name)
             };
 
-            let display_name = format_entry_name(&relative_path.to_string_lossy());
             let kind = DirEntryKind::from(&file_type);
-            entries.push(DirEntry {
-                name: display_name,
-                kind,
-            });
+            let is_dir = kind == DirEntryKind::Directory;
+            let glob_path = path_to_glob_string(&relative_path);
+
+            let gitignored = options.respect_gitignore && is_ignored(&rules, &relative_path, is_dir);
+            let excluded = options
+                .exclude_globs
+                .iter()
+                .any(|pattern| glob_match(pattern, &glob_path));
+
+            if gitignored || excluded {
+                // Prune pruned directories from the traversal stack entirely so we
+                // don't pay to read their children.
+                continue;
+            }
+
+            let included_by_glob = options.include_globs.is_empty()
+                || is_dir
+                || options
+                    .include_globs
+                    .iter()
+                    .any(|pattern| glob_match(pattern, &glob_path));
+
+            // A symlink into a directory is only recursed into when
+            // `follow_symlinks` is set and doing so wouldn't revisit a
+            // directory already on this traversal branch.
+            let mut followed_symlink_dir = None;
+            if kind == DirEntryKind::Symlink && options.follow_symlinks {
+                if let Ok(metadata) = fs::metadata(&entry.path()).await {
+                    if metadata.is_dir() {
+                        if let Ok(identity) = dir_identity(&entry.path(), &metadata).await {
+                            if ancestors.contains(&identity) {
+                                followed_symlink_dir = Some(false);
+                            } else {
+                                followed_symlink_dir = Some(true);
+                            }
+                        }
+                    }
+                }
+            }
+
+            let entry_depth = relative_path.components().count();
+            if included_by_glob && entry_depth >= options.min_depth {
+                let mut display_name = format_entry_name(&relative_path.to_string_lossy());
+                if followed_symlink_dir == Some(false) {
+                    display_name.push_str(" (cycle)");
+                }
 
-            if kind == DirEntryKind::Directory && remaining_depth > 1 {
-                stack.push((entry.path(), relative_path, remaining_depth - 1));
+                let need_metadata =
+                    !options.fields.is_empty() || options.sort_by != SortBy::Name;
+                let metadata = if need_metadata {
+                    entry_metadata(&entry.path()).await
+                } else {
+                    None
+                };
+
+                entries.push(DirEntry {
+                    name: display_name,
+                    kind,
+                    metadata,
+                });
+            }
+
+            if (is_dir || followed_symlink_dir == Some(true)) && remaining_depth > 1 {
+                stack.push((
+                    entry.path(),
+                    relative_path,
+                    remaining_depth - 1,
+                    rules.clone(),
+                    ancestors.clone(),
+                ));
             }
         }
     }
@@ -181,6 +510,132 @@ async fn collect_entries(
     Ok(())
 }
 
+/// Computes a content-addressed Merkle digest of the tree rooted at
+/// `dir_path`: each file's leaf digest is `BLAKE3(bytes)`, each symlink's
+/// leaf digest is `BLAKE3(target)`, and each directory's digest is the
+/// `BLAKE3` hash of its children (sorted by name, post-order) serialized as
+/// `(name, kind, digest, size)` tuples. Only `exclude_globs`, `include_globs`
+/// and `respect_gitignore` apply here; `follow_symlinks`, `fields`,
+/// `sort_by`, `min_depth` and `depth` are meaningless for a digest that must
+/// be stable across machines, so they're ignored.
+fn compute_digest<'a>(
+    dir_path: &'a Path,
+    relative_prefix: &'a Path,
+    parent_rules: Vec<IgnoreRule>,
+    options: &'a CollectOptions,
+    collect_map: bool,
+    out_map: &'a mut Vec<(String, blake3::Hash)>,
+) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<blake3::Hash, FunctionCallError>> + Send + 'a>>
+{
+    Box::pin(async move {
+        let rules = if options.respect_gitignore {
+            let mut rules = parent_rules;
+            rules.extend(load_gitignore_rules(dir_path, relative_prefix).await);
+            rules
+        } else {
+            parent_rules
+        };
+
+        let mut read_dir = fs::read_dir(dir_path).await.map_err(|err| {
+            FunctionCallError::RespondToModel(format!("failed to read directory: {err}"))
+        })?;
+
+        let mut children: Vec<(String, DirEntryKind, blake3::Hash, u64)> = Vec::new();
+
+        while let Some(entry) = read_dir.next_entry().await.map_err(|err| {
+            FunctionCallError::RespondToModel(format!("failed to read directory: {err}"))
+        })? {
+            let file_type = entry.file_type().await.map_err(|err| {
+                FunctionCallError::RespondToModel(format!("failed to inspect entry: {err}"))
+            })?;
+
+            let file_name = entry.file_name();
+            let relative_path = if relative_prefix.as_os_str().is_empty() {
+                PathBuf::from(&file_name)
+            } else {
+                relative_prefix.join(&file_name)
+            };
+
+            let kind = DirEntryKind::from(&file_type);
+            let is_dir = kind == DirEntryKind::Directory;
+            let glob_path = path_to_glob_string(&relative_path);
+
+            let gitignored = options.respect_gitignore && is_ignored(&rules, &relative_path, is_dir);
+            let excluded = options
+                .exclude_globs
+                .iter()
+                .any(|pattern| glob_match(pattern, &glob_path));
+            if gitignored || excluded {
+                continue;
+            }
+
+            let included_by_glob = options.include_globs.is_empty()
+                || is_dir
+                || options
+                    .include_globs
+                    .iter()
+                    .any(|pattern| glob_match(pattern, &glob_path));
+            if !included_by_glob {
+                continue;
+            }
+
+            let (digest, size) = match kind {
+                DirEntryKind::Directory => {
+                    let digest = compute_digest(
+                        &entry.path(),
+                        &relative_path,
+                        rules.clone(),
+                        options,
+                        collect_map,
+                        out_map,
+                    )
+                    .await?;
+                    (digest, 0)
+                }
+                DirEntryKind::File => {
+                    let bytes = fs::read(entry.path()).await.map_err(|err| {
+                        FunctionCallError::RespondToModel(format!("failed to read file: {err}"))
+                    })?;
+                    (blake3::hash(&bytes), bytes.len() as u64)
+                }
+                DirEntryKind::Symlink => {
+                    let target = fs::read_link(entry.path()).await.map_err(|err| {
+                        FunctionCallError::RespondToModel(format!(
+                            "failed to read symlink target: {err}"
+                        ))
+                    })?;
+                    (blake3::hash(target.to_string_lossy().as_bytes()), 0)
+                }
+                DirEntryKind::Other => (blake3::hash(&[]), 0),
+            };
+
+            if collect_map {
+                out_map.push((glob_path, digest));
+            }
+
+            children.push((
+                file_name.to_string_lossy().into_owned(),
+                kind,
+                digest,
+                size,
+            ));
+        }
+
+        children.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let mut hasher = blake3::Hasher::new();
+        for (name, kind, digest, size) in &children {
+            hasher.update(&(name.len() as u64).to_le_bytes());
+            hasher.update(name.as_bytes());
+            hasher.update(kind.label().as_bytes());
+            hasher.update(digest.as_bytes());
+            hasher.update(&size.to_le_bytes());
+        }
+
+        Ok(hasher.finalize())
+    })
+}
+
 fn format_entry_name(name: &str) -> String {
     if name.len() > MAX_ENTRY_LENGTH {
         take_bytes_at_char_boundary(name, MAX_ENTRY_LENGTH).to_string()
@@ -189,14 +644,24 @@ fn format_entry_name(name: &str) -> String {
     }
 }
 
+/// Renders a path using `/` separators regardless of platform, so glob and
+/// gitignore patterns (which are always `/`-separated) match consistently.
+pub(crate) fn path_to_glob_string(path: &Path) -> String {
+    path.components()
+        .filter_map(|component| component.as_os_str().to_str())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
 #[derive(Clone)]
 struct DirEntry {
     name: String,
     kind: DirEntryKind,
+    metadata: Option<EntryMetadata>,
 }
 
-#[derive(Clone, Copy, PartialEq, Eq)]
-enum DirEntryKind {
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum DirEntryKind {
     Directory,
     File,
     Symlink,
@@ -228,11 +693,162 @@ impl From<&FileType> for DirEntryKind {
     }
 }
 
+/// A single parsed line from a `.gitignore` file, anchored to the directory
+/// that contained it.
+#[derive(Clone)]
+pub(crate) struct IgnoreRule {
+    /// Path (relative to the walk root) of the directory the `.gitignore`
+    /// that defined this rule lives in.
+    base: PathBuf,
+    /// Pattern text with any leading/trailing slash already stripped.
+    pattern: String,
+    /// Whether the pattern is anchored to `base` (contained a `/` before the
+    /// end) rather than matching at any depth beneath it.
+    anchored: bool,
+    /// Whether the pattern only applies to directories (trailing `/`).
+    dir_only: bool,
+    /// Whether this rule re-includes a previously ignored path (`!pattern`).
+    negate: bool,
+}
+
+pub(crate) async fn load_gitignore_rules(dir: &Path, base: &Path) -> Vec<IgnoreRule> {
+    let Ok(contents) = fs::read_to_string(dir.join(GITIGNORE_FILE_NAME)).await else {
+        return Vec::new();
+    };
+
+    contents
+        .lines()
+        .filter_map(|line| parse_gitignore_line(line, base))
+        .collect()
+}
+
+fn parse_gitignore_line(line: &str, base: &Path) -> Option<IgnoreRule> {
+    let line = line.trim_end();
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+
+    let (negate, rest) = match line.strip_prefix('!') {
+        Some(rest) => (true, rest),
+        None => (false, line),
+    };
+
+    let dir_only = rest.len() > 1 && rest.ends_with('/');
+    let rest = rest.strip_suffix('/').unwrap_or(rest);
+    // A pattern containing a `/` before the end is anchored to `base`;
+    // otherwise it matches at any depth beneath `base`.
+    let anchored = rest.contains('/');
+    let pattern = rest.strip_prefix('/').unwrap_or(rest).to_string();
+    if pattern.is_empty() {
+        return None;
+    }
+
+    Some(IgnoreRule {
+        base: base.to_path_buf(),
+        pattern,
+        anchored,
+        dir_only,
+        negate,
+    })
+}
+
+pub(crate) fn is_ignored(rules: &[IgnoreRule], relative_path: &Path, is_dir: bool) -> bool {
+    let mut ignored = false;
+    for rule in rules {
+        if rule_matches(rule, relative_path, is_dir) {
+            ignored = !rule.negate;
+        }
+    }
+    ignored
+}
+
+fn rule_matches(rule: &IgnoreRule, relative_path: &Path, is_dir: bool) -> bool {
+    if rule.dir_only && !is_dir {
+        return false;
+    }
+
+    let Ok(relative_to_base) = relative_path.strip_prefix(&rule.base) else {
+        return false;
+    };
+    let candidate = path_to_glob_string(relative_to_base);
+
+    if rule.anchored {
+        glob_match(&rule.pattern, &candidate)
+    } else {
+        glob_match(&rule.pattern, &candidate) || glob_match(&format!("**/{}", rule.pattern), &candidate)
+    }
+}
+
+/// Matches `path` (always `/`-separated, see [`path_to_glob_string`]) against
+/// a glob `pattern`, where `*` and `?` operate within a single path segment
+/// and `**` matches zero or more segments, spanning directory boundaries.
+pub(crate) fn glob_match(pattern: &str, path: &str) -> bool {
+    let pattern_segments: Vec<&str> = pattern.split('/').collect();
+    let path_segments: Vec<&str> = path.split('/').collect();
+    segments_match(&pattern_segments, &path_segments)
+}
+
+fn segments_match(pattern: &[&str], path: &[&str]) -> bool {
+    match pattern.first() {
+        None => path.is_empty(),
+        Some(&"**") => {
+            if segments_match(&pattern[1..], path) {
+                return true;
+            }
+            match path.split_first() {
+                Some((_, rest)) => segments_match(pattern, rest),
+                None => false,
+            }
+        }
+        Some(segment) => match path.split_first() {
+            Some((head, rest)) => segment_match(segment, head) && segments_match(&pattern[1..], rest),
+            None => false,
+        },
+    }
+}
+
+/// Matches a single path segment against a single pattern segment containing
+/// `*` (any run of characters) and `?` (any single character).
+fn segment_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+
+    let (mut p, mut t) = (0, 0);
+    let (mut star_p, mut star_t) = (None, 0);
+
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star_p = Some(p);
+            star_t = t;
+            p += 1;
+        } else if let Some(sp) = star_p {
+            p = sp + 1;
+            star_t += 1;
+            t = star_t;
+        } else {
+            return false;
+        }
+    }
+
+    while p < pattern.len() && pattern[p] == '*' {
+        p += 1;
+    }
+
+    p == pattern.len()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use tempfile::tempdir;
 
+    fn no_filters() -> CollectOptions {
+        CollectOptions::default()
+    }
+
     #[tokio::test]
     async fn lists_directory_entries() {
         let temp = tempdir().expect("create tempdir");
@@ -265,7 +881,7 @@ mod tests {
             symlink(dir_path.join("entry.txt"), &link_path).expect("create symlink");
         }
 
-        let entries = list_dir_slice(dir_path, 1, 20, 3)
+        let entries = list_dir_slice(dir_path, 1, 20, 3, &no_filters())
             .await
             .expect("list directory");
 
@@ -299,7 +915,7 @@ mod tests {
             .await
             .expect("create sub dir");
 
-        let err = list_dir_slice(dir_path, 10, 1, 2)
+        let err = list_dir_slice(dir_path, 10, 1, 2, &no_filters())
             .await
             .expect_err("offset exceeds entries");
         assert_eq!(
@@ -326,7 +942,7 @@ mod tests {
             .await
             .expect("write deeper");
 
-        let entries_depth_one = list_dir_slice(dir_path, 1, 10, 1)
+        let entries_depth_one = list_dir_slice(dir_path, 1, 10, 1, &no_filters())
             .await
             .expect("list depth 1");
         assert_eq!(
@@ -337,7 +953,7 @@ mod tests {
             ]
         );
 
-        let entries_depth_two = list_dir_slice(dir_path, 1, 20, 2)
+        let entries_depth_two = list_dir_slice(dir_path, 1, 20, 2, &no_filters())
             .await
             .expect("list depth 2");
         assert_eq!(
@@ -350,7 +966,7 @@ mod tests {
             ]
         );
 
-        let entries_depth_three = list_dir_slice(dir_path, 1, 30, 3)
+        let entries_depth_three = list_dir_slice(dir_path, 1, 30, 3, &no_filters())
             .await
             .expect("list depth 3");
         assert_eq!(
@@ -364,4 +980,325 @@ mod tests {
             ]
         );
     }
+
+    #[tokio::test]
+    async fn excludes_entries_matching_exclude_globs() {
+        let temp = tempdir().expect("create tempdir");
+        let dir_path = temp.path();
+        tokio::fs::create_dir(dir_path.join("node_modules"))
+            .await
+            .expect("create node_modules");
+        tokio::fs::write(dir_path.join("node_modules").join("pkg.js"), b"x")
+            .await
+            .expect("write nested file");
+        tokio::fs::write(dir_path.join("main.rs"), b"fn main() {}")
+            .await
+            .expect("write main.rs");
+
+        let options = CollectOptions {
+            exclude_globs: vec!["node_modules".to_string()],
+            ..CollectOptions::default()
+        };
+
+        let entries = list_dir_slice(dir_path, 1, 10, 3, &options)
+            .await
+            .expect("list directory");
+        assert_eq!(entries, vec!["E1: [file] main.rs".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn include_globs_filter_files_but_keep_directories() {
+        let temp = tempdir().expect("create tempdir");
+        let dir_path = temp.path();
+        let nested = dir_path.join("nested");
+        tokio::fs::create_dir(&nested).await.expect("create nested");
+        tokio::fs::write(nested.join("keep.rs"), b"x")
+            .await
+            .expect("write keep.rs");
+        tokio::fs::write(nested.join("skip.txt"), b"x")
+            .await
+            .expect("write skip.txt");
+
+        let options = CollectOptions {
+            include_globs: vec!["**/*.rs".to_string()],
+            ..CollectOptions::default()
+        };
+
+        let entries = list_dir_slice(dir_path, 1, 10, 2, &options)
+            .await
+            .expect("list directory");
+        assert_eq!(
+            entries,
+            vec![
+                "E1: [dir] nested".to_string(),
+                "E2: [file] nested/keep.rs".to_string(),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn respects_gitignore_rules_and_negation() {
+        let temp = tempdir().expect("create tempdir");
+        let dir_path = temp.path();
+        tokio::fs::write(dir_path.join(".gitignore"), "*.log\n!keep.log\ntarget/\n")
+            .await
+            .expect("write .gitignore");
+        tokio::fs::write(dir_path.join("keep.log"), b"x")
+            .await
+            .expect("write keep.log");
+        tokio::fs::write(dir_path.join("drop.log"), b"x")
+            .await
+            .expect("write drop.log");
+        tokio::fs::create_dir(dir_path.join("target"))
+            .await
+            .expect("create target");
+        tokio::fs::write(dir_path.join("target").join("bin"), b"x")
+            .await
+            .expect("write target/bin");
+
+        let options = CollectOptions {
+            respect_gitignore: true,
+            ..CollectOptions::default()
+        };
+
+        let entries = list_dir_slice(dir_path, 1, 10, 3, &options)
+            .await
+            .expect("list directory");
+        assert_eq!(
+            entries,
+            vec![
+                "E1: [file] .gitignore".to_string(),
+                "E2: [file] keep.log".to_string(),
+            ]
+        );
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn follows_symlinked_directories() {
+        let temp = tempdir().expect("create tempdir");
+        let dir_path = temp.path();
+        let real_dir = dir_path.join("real");
+        tokio::fs::create_dir(&real_dir)
+            .await
+            .expect("create real dir");
+        tokio::fs::write(real_dir.join("inside.txt"), b"x")
+            .await
+            .expect("write inside.txt");
+
+        use std::os::unix::fs::symlink;
+        symlink(&real_dir, dir_path.join("alias")).expect("create symlink");
+
+        let options = CollectOptions {
+            follow_symlinks: true,
+            ..CollectOptions::default()
+        };
+
+        let entries = list_dir_slice(dir_path, 1, 10, 3, &options)
+            .await
+            .expect("list directory");
+        assert_eq!(
+            entries,
+            vec![
+                "E1: [symlink] alias".to_string(),
+                "E2: [file] alias/inside.txt".to_string(),
+                "E3: [dir] real".to_string(),
+                "E4: [file] real/inside.txt".to_string(),
+            ]
+        );
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn detects_symlink_cycles() {
+        let temp = tempdir().expect("create tempdir");
+        let dir_path = temp.path();
+
+        use std::os::unix::fs::symlink;
+        symlink(dir_path, dir_path.join("self_loop")).expect("create symlink");
+
+        let options = CollectOptions {
+            follow_symlinks: true,
+            ..CollectOptions::default()
+        };
+
+        let entries = list_dir_slice(dir_path, 1, 10, 5, &options)
+            .await
+            .expect("list directory");
+        assert_eq!(
+            entries,
+            vec!["E1: [symlink] self_loop (cycle)".to_string()]
+        );
+    }
+
+    #[tokio::test]
+    async fn renders_requested_fields() {
+        let temp = tempdir().expect("create tempdir");
+        let dir_path = temp.path();
+        tokio::fs::write(dir_path.join("entry.txt"), b"12345")
+            .await
+            .expect("write entry.txt");
+
+        let options = CollectOptions {
+            fields: vec!["size".to_string()],
+            ..CollectOptions::default()
+        };
+
+        let entries = list_dir_slice(dir_path, 1, 10, 1, &options)
+            .await
+            .expect("list directory");
+        assert_eq!(entries, vec!["E1: [file] size=5 entry.txt".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn sorts_by_size_and_supports_reverse() {
+        let temp = tempdir().expect("create tempdir");
+        let dir_path = temp.path();
+        tokio::fs::write(dir_path.join("small.txt"), b"a")
+            .await
+            .expect("write small.txt");
+        tokio::fs::write(dir_path.join("large.txt"), b"aaaaaaaaaa")
+            .await
+            .expect("write large.txt");
+
+        let options = CollectOptions {
+            sort_by: SortBy::Size,
+            reverse: true,
+            ..CollectOptions::default()
+        };
+
+        let entries = list_dir_slice(dir_path, 1, 10, 1, &options)
+            .await
+            .expect("list directory");
+        assert_eq!(
+            entries,
+            vec![
+                "E1: [file] large.txt".to_string(),
+                "E2: [file] small.txt".to_string(),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn min_depth_skips_shallow_entries() {
+        let temp = tempdir().expect("create tempdir");
+        let dir_path = temp.path();
+        let nested = dir_path.join("nested");
+        tokio::fs::create_dir(&nested).await.expect("create nested");
+        tokio::fs::write(dir_path.join("root.txt"), b"root")
+            .await
+            .expect("write root");
+        tokio::fs::write(nested.join("child.txt"), b"child")
+            .await
+            .expect("write child");
+
+        let options = CollectOptions {
+            min_depth: 2,
+            ..CollectOptions::default()
+        };
+
+        let entries = list_dir_slice(dir_path, 1, 10, 2, &options)
+            .await
+            .expect("list directory");
+        assert_eq!(entries, vec!["E1: [file] nested/child.txt".to_string()]);
+    }
+
+    async fn digest_of(dir_path: &Path, options: &CollectOptions) -> blake3::Hash {
+        let mut map = Vec::new();
+        compute_digest(dir_path, Path::new(""), Vec::new(), options, false, &mut map)
+            .await
+            .expect("compute digest")
+    }
+
+    #[tokio::test]
+    async fn digest_is_stable_across_identical_trees() {
+        let first = tempdir().expect("create tempdir");
+        let second = tempdir().expect("create tempdir");
+        for dir in [first.path(), second.path()] {
+            tokio::fs::create_dir(dir.join("nested"))
+                .await
+                .expect("create nested");
+            tokio::fs::write(dir.join("nested").join("a.txt"), b"hello")
+                .await
+                .expect("write a.txt");
+            tokio::fs::write(dir.join("root.txt"), b"world")
+                .await
+                .expect("write root.txt");
+        }
+
+        let digest_first = digest_of(first.path(), &no_filters()).await;
+        let digest_second = digest_of(second.path(), &no_filters()).await;
+        assert_eq!(digest_first, digest_second);
+    }
+
+    #[tokio::test]
+    async fn digest_changes_when_file_content_changes() {
+        let temp = tempdir().expect("create tempdir");
+        let dir_path = temp.path();
+        tokio::fs::write(dir_path.join("a.txt"), b"hello")
+            .await
+            .expect("write a.txt");
+
+        let before = digest_of(dir_path, &no_filters()).await;
+
+        tokio::fs::write(dir_path.join("a.txt"), b"goodbye")
+            .await
+            .expect("rewrite a.txt");
+        let after = digest_of(dir_path, &no_filters()).await;
+
+        assert_ne!(before, after);
+    }
+
+    #[tokio::test]
+    async fn digest_ignores_gitignored_files() {
+        // Two separate trees, identical apart from `ignored.txt`, so the only
+        // way the digests could differ is if `ignored.txt` were hashed.
+        let without_ignored = tempdir().expect("create tempdir");
+        let with_ignored = tempdir().expect("create tempdir");
+        for dir_path in [without_ignored.path(), with_ignored.path()] {
+            tokio::fs::write(dir_path.join("a.txt"), b"hello")
+                .await
+                .expect("write a.txt");
+            tokio::fs::write(dir_path.join(".gitignore"), "ignored.txt\n")
+                .await
+                .expect("write .gitignore");
+        }
+        tokio::fs::write(
+            with_ignored.path().join("ignored.txt"),
+            b"should not affect digest",
+        )
+        .await
+        .expect("write ignored.txt");
+
+        let options = CollectOptions {
+            respect_gitignore: true,
+            ..CollectOptions::default()
+        };
+
+        let without_ignored_digest = digest_of(without_ignored.path(), &options).await;
+        let with_ignored_digest = digest_of(with_ignored.path(), &options).await;
+
+        assert_eq!(without_ignored_digest, with_ignored_digest);
+    }
+
+    #[tokio::test]
+    async fn digest_map_covers_every_relative_path() {
+        let temp = tempdir().expect("create tempdir");
+        let dir_path = temp.path();
+        tokio::fs::create_dir(dir_path.join("nested"))
+            .await
+            .expect("create nested");
+        tokio::fs::write(dir_path.join("nested").join("a.txt"), b"hello")
+            .await
+            .expect("write a.txt");
+
+        let mut map = Vec::new();
+        compute_digest(dir_path, Path::new(""), Vec::new(), &no_filters(), true, &mut map)
+            .await
+            .expect("compute digest");
+
+        let mut paths: Vec<&str> = map.iter().map(|(path, _)| path.as_str()).collect();
+        paths.sort_unstable();
+        assert_eq!(paths, vec!["nested", "nested/a.txt"]);
+    }
 }
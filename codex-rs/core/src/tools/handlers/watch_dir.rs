@@ -0,0 +1,619 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::task::Context;
+use std::task::Poll;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use notify::Event;
+use notify::EventKind;
+use notify::RecommendedWatcher;
+use notify::RecursiveMode;
+use notify::Watcher;
+use serde::Deserialize;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tokio_stream::Stream;
+use tokio_stream::StreamExt;
+
+use crate::function_tool::FunctionCallError;
+use crate::tools::context::ToolInvocation;
+use crate::tools::context::ToolOutput;
+use crate::tools::context::ToolPayload;
+use crate::tools::handlers::list_dir::glob_match;
+use crate::tools::handlers::list_dir::is_ignored;
+use crate::tools::handlers::list_dir::load_gitignore_rules;
+use crate::tools::handlers::list_dir::path_to_glob_string;
+use crate::tools::handlers::list_dir::DirEntryKind;
+use crate::tools::handlers::list_dir::IgnoreRule;
+use crate::tools::registry::ToolHandler;
+use crate::tools::registry::ToolKind;
+
+const WATCH_CHANNEL_CAPACITY: usize = 1024;
+
+pub struct WatchDirHandler;
+
+fn default_recursive() -> bool {
+    true
+}
+
+fn default_poll_timeout_ms() -> u64 {
+    2_000
+}
+
+#[derive(Deserialize)]
+struct WatchDirArgs {
+    dir_path: String,
+    #[serde(default = "default_recursive")]
+    recursive: bool,
+    #[serde(default)]
+    depth: Option<usize>,
+    #[serde(default)]
+    exclude_globs: Vec<String>,
+    #[serde(default)]
+    include_globs: Vec<String>,
+    #[serde(default)]
+    respect_gitignore: bool,
+    /// How long to keep draining live deltas after the `Idle` marker before
+    /// returning, since a function call returns a single result rather than
+    /// an open-ended stream.
+    #[serde(default = "default_poll_timeout_ms")]
+    poll_timeout_ms: u64,
+}
+
+#[async_trait]
+impl ToolHandler for WatchDirHandler {
+    fn kind(&self) -> ToolKind {
+        ToolKind::Function
+    }
+
+    async fn handle(&self, invocation: ToolInvocation) -> Result<ToolOutput, FunctionCallError> {
+        let ToolInvocation { payload, .. } = invocation;
+
+        let arguments = match payload {
+            ToolPayload::Function { arguments } => arguments,
+            _ => {
+                return Err(FunctionCallError::RespondToModel(
+                    "watch_dir handler received unsupported payload".to_string(),
+                ));
+            }
+        };
+
+        let args: WatchDirArgs = serde_json::from_str(&arguments).map_err(|err| {
+            FunctionCallError::RespondToModel(format!(
+                "failed to parse function arguments: {err:?}"
+            ))
+        })?;
+
+        let WatchDirArgs {
+            dir_path,
+            recursive,
+            depth,
+            exclude_globs,
+            include_globs,
+            respect_gitignore,
+            poll_timeout_ms,
+        } = args;
+
+        let path = PathBuf::from(&dir_path);
+        let options = WatchOptions {
+            recursive,
+            depth,
+            exclude_globs,
+            include_globs,
+            respect_gitignore,
+        };
+
+        let mut stream = watch_dir(path, options).await?;
+        let mut lines = Vec::new();
+
+        while let Some(event) = stream.next().await {
+            let is_idle = event.kind == WatchEventKind::Idle;
+            lines.push(format_event(&event));
+            if is_idle {
+                break;
+            }
+        }
+
+        let deadline = tokio::time::sleep(Duration::from_millis(poll_timeout_ms));
+        tokio::pin!(deadline);
+        loop {
+            tokio::select! {
+                maybe_event = stream.next() => {
+                    match maybe_event {
+                        Some(event) => lines.push(format_event(&event)),
+                        None => break,
+                    }
+                }
+                () = &mut deadline => break,
+            }
+        }
+
+        Ok(ToolOutput::Function {
+            content: lines.join("\n"),
+            success: Some(true),
+        })
+    }
+}
+
+fn format_event(event: &WatchEvent) -> String {
+    match event.kind {
+        WatchEventKind::Existing => format!("E: {}", event.relative_path),
+        WatchEventKind::Idle => "---".to_string(),
+        WatchEventKind::Added => format!("+: {}", event.relative_path),
+        WatchEventKind::Removed => format!("-: {}", event.relative_path),
+    }
+}
+
+/// What happened to a watched path.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum WatchEventKind {
+    /// Reported once per entry already on disk when the watch started, so a
+    /// consumer can build its initial view before live deltas arrive.
+    Existing,
+    /// Marks the end of the `Existing` batch; no relative path is attached.
+    Idle,
+    Added,
+    Removed,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct WatchEvent {
+    pub(crate) kind: WatchEventKind,
+    pub(crate) relative_path: String,
+    pub(crate) entry_kind: Option<DirEntryKind>,
+}
+
+/// Same glob/ignore knobs `ListDirHandler` offers, plus a `recursive`/`depth`
+/// bound. The initial `Existing` scan applies all of them and prunes excluded
+/// or ignored directories. Live `Added`/`Removed` events are checked only
+/// against `depth` and the globs, each by its own relative path:
+/// `respect_gitignore` is not applied to them, and an excluded directory does
+/// not hide live events for paths beneath it unless a glob matches those
+/// paths too (e.g. `target/**`).
+#[derive(Clone)]
+pub(crate) struct WatchOptions {
+    pub(crate) recursive: bool,
+    pub(crate) depth: Option<usize>,
+    pub(crate) exclude_globs: Vec<String>,
+    pub(crate) include_globs: Vec<String>,
+    pub(crate) respect_gitignore: bool,
+}
+
+impl Default for WatchOptions {
+    fn default() -> Self {
+        Self {
+            recursive: true,
+            depth: None,
+            exclude_globs: Vec::new(),
+            include_globs: Vec::new(),
+            respect_gitignore: false,
+        }
+    }
+}
+
+fn passes_filters(options: &WatchOptions, relative_path: &str, is_dir: bool) -> bool {
+    if options
+        .exclude_globs
+        .iter()
+        .any(|pattern| glob_match(pattern, relative_path))
+    {
+        return false;
+    }
+
+    if options.include_globs.is_empty() || is_dir {
+        return true;
+    }
+
+    options
+        .include_globs
+        .iter()
+        .any(|pattern| glob_match(pattern, relative_path))
+}
+
+fn within_depth_bound(relative_path: &Path, depth: Option<usize>) -> bool {
+    match depth {
+        Some(depth) => relative_path.components().count() <= depth,
+        None => true,
+    }
+}
+
+/// Live deltas are buffered here while the initial scan is still walking the
+/// tree, then replayed after the `Idle` marker, so a consumer never sees a
+/// live event interleaved with (or ahead of) the `Existing` batch.
+enum PendingEvents {
+    Buffering(Vec<WatchEvent>),
+    Live,
+}
+
+/// A live stream of [`WatchEvent`]s for `dir_path`. Dropping the stream tears
+/// down the underlying OS watch descriptors (the `RecommendedWatcher` is
+/// unregistered on drop), making it cancel-safe.
+#[derive(Debug)]
+pub(crate) struct DirWatchStream {
+    _watcher: RecommendedWatcher,
+    receiver: ReceiverStream<WatchEvent>,
+}
+
+impl Stream for DirWatchStream {
+    type Item = WatchEvent;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        Pin::new(&mut this.receiver).poll_next(cx)
+    }
+}
+
+/// Starts watching `dir_path` for changes. Emits an `Existing` event for
+/// every entry currently on disk (respecting `options`), then an `Idle`
+/// marker, and finally live `Added`/`Removed` events as they occur.
+pub(crate) async fn watch_dir(
+    dir_path: PathBuf,
+    options: WatchOptions,
+) -> Result<DirWatchStream, FunctionCallError> {
+    let (tx, rx) = mpsc::channel(WATCH_CHANNEL_CAPACITY);
+
+    if !dir_path.is_absolute() {
+        return Err(FunctionCallError::RespondToModel(
+            "dir_path must be an absolute path".to_string(),
+        ));
+    }
+
+    let recursive_mode = if options.recursive {
+        RecursiveMode::Recursive
+    } else {
+        RecursiveMode::NonRecursive
+    };
+
+    // Tracks the kind of every path we've seen, so a `Removed` event (whose
+    // path no longer exists on disk by the time the callback runs) can still
+    // report whether it was a directory instead of always reading `None`.
+    let known_kinds: Arc<Mutex<HashMap<PathBuf, DirEntryKind>>> =
+        Arc::new(Mutex::new(HashMap::new()));
+    let pending: Arc<Mutex<PendingEvents>> = Arc::new(Mutex::new(PendingEvents::Buffering(Vec::new())));
+
+    let watch_root = dir_path.clone();
+    let event_tx = tx.clone();
+    let callback_options = options.clone();
+    let callback_kinds = known_kinds.clone();
+    let callback_pending = pending.clone();
+    let mut watcher = notify::recommended_watcher(move |result: notify::Result<Event>| {
+        let Ok(event) = result else {
+            return;
+        };
+        let Some(kind) = classify_event(&event.kind) else {
+            return;
+        };
+
+        for path in &event.paths {
+            let Ok(relative) = path.strip_prefix(&watch_root) else {
+                continue;
+            };
+            if relative.as_os_str().is_empty() {
+                continue;
+            }
+
+            let entry_kind = if kind == WatchEventKind::Removed {
+                callback_kinds
+                    .lock()
+                    .unwrap_or_else(|err| err.into_inner())
+                    .remove(path)
+            } else {
+                let entry_kind = std::fs::symlink_metadata(path)
+                    .ok()
+                    .map(|metadata| DirEntryKind::from(&metadata.file_type()));
+                if let Some(entry_kind) = entry_kind {
+                    callback_kinds
+                        .lock()
+                        .unwrap_or_else(|err| err.into_inner())
+                        .insert(path.clone(), entry_kind);
+                }
+                entry_kind
+            };
+            let is_dir = entry_kind == Some(DirEntryKind::Directory);
+            let relative_path = path_to_glob_string(relative);
+
+            if !within_depth_bound(relative, callback_options.depth) {
+                continue;
+            }
+            if !passes_filters(&callback_options, &relative_path, is_dir) {
+                continue;
+            }
+
+            let event = WatchEvent {
+                kind,
+                relative_path,
+                entry_kind,
+            };
+
+            let mut pending = callback_pending
+                .lock()
+                .unwrap_or_else(|err| err.into_inner());
+            match &mut *pending {
+                PendingEvents::Buffering(buffered) => buffered.push(event),
+                PendingEvents::Live => {
+                    let _ = event_tx.try_send(event);
+                }
+            }
+        }
+    })
+    .map_err(|err| {
+        FunctionCallError::RespondToModel(format!("failed to start filesystem watcher: {err}"))
+    })?;
+
+    watcher
+        .watch(&dir_path, recursive_mode)
+        .map_err(|err| FunctionCallError::RespondToModel(format!("failed to watch directory: {err}")))?;
+
+    let initial_root = dir_path.clone();
+    let initial_options = options.clone();
+    let initial_tx = tx;
+    let scan_kinds = known_kinds;
+    let scan_pending = pending;
+    tokio::spawn(async move {
+        emit_existing_entries(&initial_root, &initial_options, &initial_tx, &scan_kinds).await;
+
+        let _ = initial_tx
+            .send(WatchEvent {
+                kind: WatchEventKind::Idle,
+                relative_path: String::new(),
+                entry_kind: None,
+            })
+            .await;
+
+        // Drain whatever the watcher callback buffered while the scan (and
+        // the `Idle` send above) were running, re-checking after every
+        // batch. Only flip to `Live` in the same critical section where the
+        // buffer is observed empty, so the callback can never start sending
+        // directly to `tx` until every buffered event has already been
+        // fully sent ahead of it.
+        loop {
+            let batch = {
+                let mut pending = scan_pending.lock().unwrap_or_else(|err| err.into_inner());
+                match &mut *pending {
+                    PendingEvents::Buffering(buffered) if buffered.is_empty() => {
+                        *pending = PendingEvents::Live;
+                        break;
+                    }
+                    PendingEvents::Buffering(buffered) => std::mem::take(buffered),
+                    PendingEvents::Live => unreachable!("only this task transitions to Live"),
+                }
+            };
+            for event in batch {
+                let _ = initial_tx.send(event).await;
+            }
+        }
+    });
+
+    Ok(DirWatchStream {
+        _watcher: watcher,
+        receiver: ReceiverStream::new(rx),
+    })
+}
+
+fn classify_event(kind: &EventKind) -> Option<WatchEventKind> {
+    match kind {
+        EventKind::Create(_) => Some(WatchEventKind::Added),
+        EventKind::Remove(_) => Some(WatchEventKind::Removed),
+        _ => None,
+    }
+}
+
+async fn emit_existing_entries(
+    dir_path: &Path,
+    options: &WatchOptions,
+    tx: &mpsc::Sender<WatchEvent>,
+    known_kinds: &Mutex<HashMap<PathBuf, DirEntryKind>>,
+) {
+    let mut stack = vec![(dir_path.to_path_buf(), PathBuf::new(), Vec::<IgnoreRule>::new())];
+
+    while let Some((current_dir, prefix, parent_rules)) = stack.pop() {
+        let rules = if options.respect_gitignore {
+            let mut rules = parent_rules;
+            rules.extend(load_gitignore_rules(&current_dir, &prefix).await);
+            rules
+        } else {
+            parent_rules
+        };
+
+        let Ok(mut read_dir) = tokio::fs::read_dir(&current_dir).await else {
+            continue;
+        };
+
+        while let Ok(Some(entry)) = read_dir.next_entry().await {
+            let Ok(file_type) = entry.file_type().await else {
+                continue;
+            };
+
+            let file_name = entry.file_name();
+            let relative_path = if prefix.as_os_str().is_empty() {
+                PathBuf::from(&file_name)
+            } else {
+                prefix.join(&file_name)
+            };
+
+            let entry_kind = DirEntryKind::from(&file_type);
+            let is_dir = entry_kind == DirEntryKind::Directory;
+            let glob_path = path_to_glob_string(&relative_path);
+
+            known_kinds
+                .lock()
+                .unwrap_or_else(|err| err.into_inner())
+                .insert(entry.path(), entry_kind);
+
+            if options.respect_gitignore && is_ignored(&rules, &relative_path, is_dir) {
+                continue;
+            }
+            if !within_depth_bound(&relative_path, options.depth) {
+                continue;
+            }
+            if !passes_filters(options, &glob_path, is_dir) {
+                continue;
+            }
+
+            let _ = tx
+                .send(WatchEvent {
+                    kind: WatchEventKind::Existing,
+                    relative_path: glob_path,
+                    entry_kind: Some(entry_kind),
+                })
+                .await;
+
+            if is_dir && options.recursive {
+                stack.push((entry.path(), relative_path, rules.clone()));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn no_filters() -> WatchOptions {
+        WatchOptions::default()
+    }
+
+    #[test]
+    fn classify_event_maps_create_and_remove() {
+        assert_eq!(
+            classify_event(&EventKind::Create(notify::event::CreateKind::File)),
+            Some(WatchEventKind::Added)
+        );
+        assert_eq!(
+            classify_event(&EventKind::Remove(notify::event::RemoveKind::File)),
+            Some(WatchEventKind::Removed)
+        );
+        assert_eq!(classify_event(&EventKind::Other), None);
+    }
+
+    #[test]
+    fn within_depth_bound_respects_limit() {
+        let path = Path::new("a/b/c");
+        assert!(within_depth_bound(path, None));
+        assert!(within_depth_bound(path, Some(3)));
+        assert!(!within_depth_bound(path, Some(2)));
+    }
+
+    #[test]
+    fn passes_filters_always_keeps_directories() {
+        let options = WatchOptions {
+            include_globs: vec!["**/*.rs".to_string()],
+            ..WatchOptions::default()
+        };
+
+        assert!(passes_filters(&options, "src", true));
+        assert!(!passes_filters(&options, "README.md", false));
+        assert!(passes_filters(&options, "src/lib.rs", false));
+    }
+
+    #[test]
+    fn passes_filters_honors_exclude_over_include() {
+        let options = WatchOptions {
+            include_globs: vec!["**/*.rs".to_string()],
+            exclude_globs: vec!["target/**".to_string()],
+            ..WatchOptions::default()
+        };
+
+        assert!(!passes_filters(&options, "target/debug/build.rs", false));
+    }
+
+    #[tokio::test]
+    async fn emit_existing_entries_reports_every_entry_once() {
+        let temp = tempdir().expect("create tempdir");
+        let dir_path = temp.path();
+
+        tokio::fs::create_dir(dir_path.join("nested"))
+            .await
+            .expect("create sub dir");
+        tokio::fs::write(dir_path.join("entry.txt"), b"content")
+            .await
+            .expect("write file");
+        tokio::fs::write(dir_path.join("nested/child.txt"), b"child")
+            .await
+            .expect("write child");
+
+        let (tx, mut rx) = mpsc::channel(16);
+        let known_kinds = Mutex::new(HashMap::new());
+        emit_existing_entries(dir_path, &no_filters(), &tx, &known_kinds).await;
+        drop(tx);
+
+        let mut seen = Vec::new();
+        while let Some(event) = rx.recv().await {
+            assert_eq!(event.kind, WatchEventKind::Existing);
+            seen.push(event.relative_path);
+        }
+        seen.sort();
+
+        assert_eq!(
+            seen,
+            vec![
+                "entry.txt".to_string(),
+                "nested".to_string(),
+                "nested/child.txt".to_string(),
+            ]
+        );
+        assert_eq!(
+            known_kinds
+                .lock()
+                .unwrap()
+                .get(&dir_path.join("nested"))
+                .copied(),
+            Some(DirEntryKind::Directory)
+        );
+    }
+
+    #[tokio::test]
+    async fn watch_dir_rejects_relative_paths() {
+        let err = watch_dir(PathBuf::from("relative"), WatchOptions::default())
+            .await
+            .expect_err("relative dir_path must be rejected");
+        assert_eq!(
+            err,
+            FunctionCallError::RespondToModel("dir_path must be an absolute path".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn watch_dir_emits_existing_then_idle_then_added() {
+        let temp = tempdir().expect("create tempdir");
+        let dir_path = temp.path().to_path_buf();
+
+        tokio::fs::write(dir_path.join("entry.txt"), b"content")
+            .await
+            .expect("write file");
+
+        let mut stream = watch_dir(dir_path.clone(), WatchOptions::default())
+            .await
+            .expect("start watch");
+
+        let existing = tokio::time::timeout(Duration::from_secs(5), stream.next())
+            .await
+            .expect("existing event")
+            .expect("stream open");
+        assert_eq!(existing.kind, WatchEventKind::Existing);
+        assert_eq!(existing.relative_path, "entry.txt");
+
+        let idle = tokio::time::timeout(Duration::from_secs(5), stream.next())
+            .await
+            .expect("idle event")
+            .expect("stream open");
+        assert_eq!(idle.kind, WatchEventKind::Idle);
+
+        tokio::fs::write(dir_path.join("added.txt"), b"new")
+            .await
+            .expect("write new file");
+
+        let added = tokio::time::timeout(Duration::from_secs(5), stream.next())
+            .await
+            .expect("added event")
+            .expect("stream open");
+        assert_eq!(added.kind, WatchEventKind::Added);
+        assert_eq!(added.relative_path, "added.txt");
+    }
+}